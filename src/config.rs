@@ -4,24 +4,75 @@ use std::env;
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub access_key: String,
-    pub secret_key: String,
-    pub bucket_name: String,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+    pub bucket_name: Option<String>,
     pub aws_region: String,
     pub s3_endpoint: Option<String>,
+    pub webdav_url: Option<String>,
+    pub webdav_user: Option<String>,
+    pub webdav_password: Option<String>,
+    pub azure_account: Option<String>,
+    pub azure_container: Option<String>,
+    pub azure_key: Option<String>,
+    pub azure_sas_token: Option<String>,
+    pub azure_endpoint: Option<String>,
+    pub gcs_bucket: Option<String>,
+    pub gcs_service_account_key: Option<String>,
+    pub gcs_endpoint: Option<String>,
     overrides: HashMap<String, String>,
 }
 
+/// Connection settings for one storage provider, selected by `--storage`.
+#[derive(Debug, Clone)]
+pub enum BackendConfig {
+    S3 {
+        access_key: Option<String>,
+        secret_key: Option<String>,
+        bucket_name: String,
+        region: String,
+        endpoint: Option<String>,
+    },
+    WebDav {
+        url: String,
+        user: Option<String>,
+        password: Option<String>,
+    },
+    Azure {
+        account: String,
+        container: String,
+        access_key: Option<String>,
+        sas_token: Option<String>,
+        endpoint: Option<String>,
+    },
+    Gcs {
+        bucket_name: String,
+        service_account_key: Option<String>,
+        endpoint: Option<String>,
+    },
+}
+
 impl Config {
     pub fn from_env() -> Result<Self> {
-        let access_key =
-            env::var("S3_ACCESS_KEY").context("S3_ACCESS_KEY environment variable is required")?;
-        let secret_key =
-            env::var("S3_SECRET_KEY").context("S3_SECRET_KEY environment variable is required")?;
-        let bucket_name = env::var("S3_BUCKET_NAME")
-            .context("S3_BUCKET_NAME environment variable is required")?;
+        // Static keys and the bucket name are optional here: which ones are actually
+        // required depends on the storage backend selected, and is enforced in
+        // `backend_config` instead of up front.
+        let access_key = env::var("S3_ACCESS_KEY").ok();
+        let secret_key = env::var("S3_SECRET_KEY").ok();
+        let bucket_name = env::var("S3_BUCKET_NAME").ok();
         let aws_region = env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
         let s3_endpoint = env::var("S3_ENDPOINT").ok();
+        let webdav_url = env::var("WEBDAV_URL").ok();
+        let webdav_user = env::var("WEBDAV_USER").ok();
+        let webdav_password = env::var("WEBDAV_PASSWORD").ok();
+        let azure_account = env::var("AZURE_STORAGE_ACCOUNT").ok();
+        let azure_container = env::var("AZURE_STORAGE_CONTAINER").ok();
+        let azure_key = env::var("AZURE_STORAGE_KEY").ok();
+        let azure_sas_token = env::var("AZURE_STORAGE_SAS_TOKEN").ok();
+        let azure_endpoint = env::var("AZURE_STORAGE_ENDPOINT").ok();
+        let gcs_bucket = env::var("GCS_BUCKET_NAME").ok();
+        let gcs_service_account_key = env::var("GCS_SERVICE_ACCOUNT_KEY").ok();
+        let gcs_endpoint = env::var("GCS_ENDPOINT").ok();
 
         Ok(Config {
             access_key,
@@ -29,6 +80,17 @@ impl Config {
             bucket_name,
             aws_region,
             s3_endpoint,
+            webdav_url,
+            webdav_user,
+            webdav_password,
+            azure_account,
+            azure_container,
+            azure_key,
+            azure_sas_token,
+            azure_endpoint,
+            gcs_bucket,
+            gcs_service_account_key,
+            gcs_endpoint,
             overrides: HashMap::new(),
         })
     }
@@ -37,25 +99,74 @@ impl Config {
         self.overrides.insert(key.to_string(), value.to_string());
     }
 
-    pub fn get_access_key(&self) -> &str {
+    /// Builds the connection settings for `backend` (as passed to `--storage`),
+    /// failing with a precise error if a parameter that backend requires is missing.
+    /// Credentials are not checked here: Azure's key-or-SAS-token requirement is
+    /// enforced by `AzureStorage::new`, and S3 keys may come from the credential chain.
+    pub fn backend_config(&self, backend: &str) -> Result<BackendConfig> {
+        match backend {
+            "s3" => Ok(BackendConfig::S3 {
+                access_key: self.get_access_key().map(str::to_string),
+                secret_key: self.get_secret_key().map(str::to_string),
+                bucket_name: self
+                    .get_bucket_name()
+                    .context("S3_BUCKET_NAME environment variable is required for the s3 backend")?
+                    .to_string(),
+                region: self.get_aws_region().to_string(),
+                endpoint: self.get_s3_endpoint().map(str::to_string),
+            }),
+            "webdav" => Ok(BackendConfig::WebDav {
+                url: self
+                    .get_webdav_url()
+                    .context("WEBDAV_URL environment variable is required for the webdav backend")?
+                    .to_string(),
+                user: self.get_webdav_user().map(str::to_string),
+                password: self.get_webdav_password().map(str::to_string),
+            }),
+            "azure" => Ok(BackendConfig::Azure {
+                account: self
+                    .get_azure_account()
+                    .context("AZURE_STORAGE_ACCOUNT environment variable is required for the azure backend")?
+                    .to_string(),
+                container: self
+                    .get_azure_container()
+                    .context("AZURE_STORAGE_CONTAINER environment variable is required for the azure backend")?
+                    .to_string(),
+                access_key: self.get_azure_key().map(str::to_string),
+                sas_token: self.get_azure_sas_token().map(str::to_string),
+                endpoint: self.get_azure_endpoint().map(str::to_string),
+            }),
+            "gcs" => Ok(BackendConfig::Gcs {
+                bucket_name: self
+                    .get_gcs_bucket()
+                    .context("GCS_BUCKET_NAME environment variable is required for the gcs backend")?
+                    .to_string(),
+                service_account_key: self.get_gcs_service_account_key().map(str::to_string),
+                endpoint: self.get_gcs_endpoint().map(str::to_string),
+            }),
+            other => anyhow::bail!("Unknown storage backend: {}", other),
+        }
+    }
+
+    pub fn get_access_key(&self) -> Option<&str> {
         self.overrides
             .get("ACCESS_KEY")
             .map(|s| s.as_str())
-            .unwrap_or(&self.access_key)
+            .or(self.access_key.as_deref())
     }
 
-    pub fn get_secret_key(&self) -> &str {
+    pub fn get_secret_key(&self) -> Option<&str> {
         self.overrides
             .get("SECRET_KEY")
             .map(|s| s.as_str())
-            .unwrap_or(&self.secret_key)
+            .or(self.secret_key.as_deref())
     }
 
-    pub fn get_bucket_name(&self) -> &str {
+    pub fn get_bucket_name(&self) -> Option<&str> {
         self.overrides
             .get("BUCKET_NAME")
             .map(|s| s.as_str())
-            .unwrap_or(&self.bucket_name)
+            .or(self.bucket_name.as_deref())
     }
 
     pub fn get_aws_region(&self) -> &str {
@@ -71,4 +182,81 @@ impl Config {
             .map(|s| s.as_str())
             .or(self.s3_endpoint.as_deref())
     }
+
+    pub fn get_webdav_url(&self) -> Option<&str> {
+        self.overrides
+            .get("WEBDAV_URL")
+            .map(|s| s.as_str())
+            .or(self.webdav_url.as_deref())
+    }
+
+    pub fn get_webdav_user(&self) -> Option<&str> {
+        self.overrides
+            .get("WEBDAV_USER")
+            .map(|s| s.as_str())
+            .or(self.webdav_user.as_deref())
+    }
+
+    pub fn get_webdav_password(&self) -> Option<&str> {
+        self.overrides
+            .get("WEBDAV_PASSWORD")
+            .map(|s| s.as_str())
+            .or(self.webdav_password.as_deref())
+    }
+
+    pub fn get_azure_account(&self) -> Option<&str> {
+        self.overrides
+            .get("AZURE_STORAGE_ACCOUNT")
+            .map(|s| s.as_str())
+            .or(self.azure_account.as_deref())
+    }
+
+    pub fn get_azure_container(&self) -> Option<&str> {
+        self.overrides
+            .get("AZURE_STORAGE_CONTAINER")
+            .map(|s| s.as_str())
+            .or(self.azure_container.as_deref())
+    }
+
+    pub fn get_azure_key(&self) -> Option<&str> {
+        self.overrides
+            .get("AZURE_STORAGE_KEY")
+            .map(|s| s.as_str())
+            .or(self.azure_key.as_deref())
+    }
+
+    pub fn get_azure_sas_token(&self) -> Option<&str> {
+        self.overrides
+            .get("AZURE_STORAGE_SAS_TOKEN")
+            .map(|s| s.as_str())
+            .or(self.azure_sas_token.as_deref())
+    }
+
+    pub fn get_azure_endpoint(&self) -> Option<&str> {
+        self.overrides
+            .get("AZURE_STORAGE_ENDPOINT")
+            .map(|s| s.as_str())
+            .or(self.azure_endpoint.as_deref())
+    }
+
+    pub fn get_gcs_bucket(&self) -> Option<&str> {
+        self.overrides
+            .get("GCS_BUCKET_NAME")
+            .map(|s| s.as_str())
+            .or(self.gcs_bucket.as_deref())
+    }
+
+    pub fn get_gcs_service_account_key(&self) -> Option<&str> {
+        self.overrides
+            .get("GCS_SERVICE_ACCOUNT_KEY")
+            .map(|s| s.as_str())
+            .or(self.gcs_service_account_key.as_deref())
+    }
+
+    pub fn get_gcs_endpoint(&self) -> Option<&str> {
+        self.overrides
+            .get("GCS_ENDPOINT")
+            .map(|s| s.as_str())
+            .or(self.gcs_endpoint.as_deref())
+    }
 }
@@ -0,0 +1,302 @@
+use anyhow::{Context, Result};
+use s3::creds::Credentials;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
+
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/latest/api/token";
+const IMDS_ROLE_URL: &str = "http://169.254.169.254/latest/meta-data/iam/security-credentials/";
+const STS_ENDPOINT: &str = "https://sts.amazonaws.com/";
+
+/// How far ahead of the real expiry we treat temporary credentials as stale, so a
+/// long-running upload doesn't start a request with credentials that expire mid-flight.
+const EXPIRY_SKEW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+struct ResolvedCredentials {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    expiration: Option<SystemTime>,
+}
+
+/// Resolves AWS credentials the way the AWS SDKs do: explicit configuration first,
+/// then the shared credentials file, then web identity federation, then EC2/ECS
+/// instance metadata.
+pub struct CredentialResolver {
+    explicit_access_key: Option<String>,
+    explicit_secret_key: Option<String>,
+    cached: Mutex<Option<ResolvedCredentials>>,
+}
+
+impl CredentialResolver {
+    pub fn new(explicit_access_key: Option<String>, explicit_secret_key: Option<String>) -> Self {
+        Self {
+            explicit_access_key,
+            explicit_secret_key,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub async fn resolve(&self) -> Result<Credentials> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(creds) = cached.as_ref() {
+            if !is_stale(creds.expiration) {
+                return to_s3_credentials(creds);
+            }
+        }
+
+        let resolved = self.fetch().await?;
+        let creds = to_s3_credentials(&resolved)?;
+        *cached = Some(resolved);
+        Ok(creds)
+    }
+
+    async fn fetch(&self) -> Result<ResolvedCredentials> {
+        if let Some(creds) = self.from_explicit_config() {
+            return Ok(creds);
+        }
+
+        if let Some(creds) = self.from_shared_credentials_file()? {
+            return Ok(creds);
+        }
+
+        if let Some(creds) = self.from_web_identity().await? {
+            return Ok(creds);
+        }
+
+        if let Some(creds) = self.from_instance_metadata().await? {
+            return Ok(creds);
+        }
+
+        anyhow::bail!(
+            "No AWS credentials found. Set S3_ACCESS_KEY/S3_SECRET_KEY, configure \
+            ~/.aws/credentials, set AWS_WEB_IDENTITY_TOKEN_FILE + AWS_ROLE_ARN, or \
+            run on an EC2/ECS host with an attached IAM role."
+        )
+    }
+
+    fn from_explicit_config(&self) -> Option<ResolvedCredentials> {
+        match (&self.explicit_access_key, &self.explicit_secret_key) {
+            (Some(access_key), Some(secret_key)) => Some(ResolvedCredentials {
+                access_key: access_key.clone(),
+                secret_key: secret_key.clone(),
+                session_token: None,
+                expiration: None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn from_shared_credentials_file(&self) -> Result<Option<ResolvedCredentials>> {
+        let Some(home) = env::var_os("HOME") else {
+            return Ok(None);
+        };
+        let path = PathBuf::from(home).join(".aws/credentials");
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let profile = env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let Some(section) = parse_ini_section(&contents, &profile) else {
+            return Ok(None);
+        };
+
+        let (Some(access_key), Some(secret_key)) = (
+            section.get("aws_access_key_id").cloned(),
+            section.get("aws_secret_access_key").cloned(),
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(ResolvedCredentials {
+            access_key,
+            secret_key,
+            session_token: section.get("aws_session_token").cloned(),
+            expiration: None,
+        }))
+    }
+
+    async fn from_web_identity(&self) -> Result<Option<ResolvedCredentials>> {
+        let (Ok(token_file), Ok(role_arn)) = (
+            env::var("AWS_WEB_IDENTITY_TOKEN_FILE"),
+            env::var("AWS_ROLE_ARN"),
+        ) else {
+            return Ok(None);
+        };
+
+        let token = tokio::fs::read_to_string(&token_file)
+            .await
+            .with_context(|| format!("Failed to read web identity token: {}", token_file))?;
+
+        let session_name = env::var("AWS_ROLE_SESSION_NAME").unwrap_or_else(|_| "gold".to_string());
+        let client = reqwest::Client::new();
+        let response = client
+            .get(STS_ENDPOINT)
+            .query(&[
+                ("Action", "AssumeRoleWithWebIdentity"),
+                ("Version", "2011-06-15"),
+                ("RoleArn", role_arn.as_str()),
+                ("RoleSessionName", session_name.as_str()),
+                ("WebIdentityToken", token.trim()),
+            ])
+            .send()
+            .await
+            .context("Failed to call sts:AssumeRoleWithWebIdentity")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "sts:AssumeRoleWithWebIdentity failed with status {}",
+                response.status()
+            );
+        }
+
+        let body = response.text().await?;
+        let access_key = extract_xml_tag(&body, "AccessKeyId")
+            .context("Missing AccessKeyId in AssumeRoleWithWebIdentity response")?;
+        let secret_key = extract_xml_tag(&body, "SecretAccessKey")
+            .context("Missing SecretAccessKey in AssumeRoleWithWebIdentity response")?;
+        let session_token = extract_xml_tag(&body, "SessionToken");
+        let expiration = extract_xml_tag(&body, "Expiration").and_then(|e| parse_rfc3339(&e));
+
+        Ok(Some(ResolvedCredentials {
+            access_key,
+            secret_key,
+            session_token,
+            expiration,
+        }))
+    }
+
+    async fn from_instance_metadata(&self) -> Result<Option<ResolvedCredentials>> {
+        let client = reqwest::Client::new();
+
+        let token_response = client
+            .put(IMDS_TOKEN_URL)
+            .header("X-aws-ec2-metadata-token-ttl-seconds", "21600")
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await;
+
+        let Ok(token_response) = token_response else {
+            // Not running on EC2/ECS, or the metadata endpoint is unreachable.
+            return Ok(None);
+        };
+        if !token_response.status().is_success() {
+            return Ok(None);
+        }
+        let token = token_response.text().await?;
+
+        let role_response = client
+            .get(IMDS_ROLE_URL)
+            .header("X-aws-ec2-metadata-token", &token)
+            .send()
+            .await
+            .context("Failed to list instance metadata roles")?;
+        if !role_response.status().is_success() {
+            return Ok(None);
+        }
+        let role = role_response.text().await?;
+        let role = role.trim();
+        if role.is_empty() {
+            return Ok(None);
+        }
+
+        let creds_response = client
+            .get(format!("{}{}", IMDS_ROLE_URL, role))
+            .header("X-aws-ec2-metadata-token", &token)
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch instance metadata credentials for role {}", role))?;
+
+        let creds_json: serde_json::Value = creds_response.json().await?;
+        let access_key = creds_json["AccessKeyId"]
+            .as_str()
+            .context("Missing AccessKeyId in instance metadata credentials")?
+            .to_string();
+        let secret_key = creds_json["SecretAccessKey"]
+            .as_str()
+            .context("Missing SecretAccessKey in instance metadata credentials")?
+            .to_string();
+        let session_token = creds_json["Token"].as_str().map(|s| s.to_string());
+        let expiration = creds_json["Expiration"].as_str().and_then(parse_rfc3339);
+
+        Ok(Some(ResolvedCredentials {
+            access_key,
+            secret_key,
+            session_token,
+            expiration,
+        }))
+    }
+}
+
+fn is_stale(expiration: Option<SystemTime>) -> bool {
+    match expiration {
+        Some(expiry) => match expiry.checked_sub(EXPIRY_SKEW) {
+            Some(refresh_at) => SystemTime::now() >= refresh_at,
+            None => true,
+        },
+        None => false,
+    }
+}
+
+fn to_s3_credentials(creds: &ResolvedCredentials) -> Result<Credentials> {
+    Credentials::new(
+        Some(&creds.access_key),
+        Some(&creds.secret_key),
+        creds.session_token.as_deref(),
+        None,
+        None,
+    )
+    .context("Failed to build S3 credentials")
+}
+
+/// Minimal INI-style parser for `~/.aws/credentials`: good enough for the
+/// `[profile]` / `key = value` shape that file always takes.
+fn parse_ini_section(contents: &str, profile: &str) -> Option<HashMap<String, String>> {
+    let header = format!("[{}]", profile);
+    let mut in_section = false;
+    let mut values = HashMap::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = line == header;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            values.insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+/// Pulls `<Tag>value</Tag>` out of an XML body without a full XML parser - the STS
+/// responses we care about are flat enough that this is reliable in practice.
+fn extract_xml_tag(body: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&close)? + start;
+    Some(body[start..end].to_string())
+}
+
+fn parse_rfc3339(value: &str) -> Option<SystemTime> {
+    humantime::parse_rfc3339(value).ok()
+}
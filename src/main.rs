@@ -1,12 +1,14 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
+use std::time::Duration;
 
 pub mod config;
+pub mod credentials;
 pub mod storage;
 pub mod uploader;
 
 use config::Config;
-use storage::{StorageBackend, S3Storage};
+use storage::{AzureStorage, GcsStorage, S3Storage, StorageBackend, WebDavStorage};
 use uploader::ReleaseUploader;
 
 #[derive(Parser)]
@@ -37,6 +39,15 @@ enum Commands {
         /// Additional configuration variables
         #[arg(long = "config", value_parser = parse_config)]
         config_overrides: Vec<(String, String)>,
+
+        /// Emit a presigned download URL for each asset, valid for the given duration
+        /// (e.g. "1h", "30m", "7d"). Not all storage backends support presigning.
+        #[arg(long, value_parser = parse_duration)]
+        presign: Option<Duration>,
+
+        /// Number of assets to upload concurrently
+        #[arg(long, default_value_t = uploader::DEFAULT_JOBS)]
+        jobs: usize,
     },
 }
 
@@ -48,6 +59,10 @@ fn parse_config(s: &str) -> Result<(String, String), String> {
     Ok((parts[0].to_string(), parts[1].to_string()))
 }
 
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    humantime::parse_duration(s).map_err(|e| e.to_string())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv::dotenv().ok();
@@ -61,6 +76,8 @@ async fn main() -> Result<()> {
             files,
             storage,
             config_overrides,
+            presign,
+            jobs,
         } => {
             let mut config = Config::from_env()?;
 
@@ -69,20 +86,21 @@ async fn main() -> Result<()> {
                 config.set_override(&key, &value);
             }
 
-            // Validate configuration before proceeding
-            config.validate()
+            let backend_config = config
+                .backend_config(&storage)
                 .with_context(|| "Configuration validation failed")?;
 
-            let storage_backend: Box<dyn StorageBackend> = match storage.as_str() {
-                "s3" => Box::new(S3Storage::new(&config).await?),
-                "webdav" => {
-                    anyhow::bail!("WebDAV storage backend not implemented yet");
-                }
-                _ => anyhow::bail!("Unknown storage backend: {}", storage),
+            let storage_backend: Box<dyn StorageBackend> = match &backend_config {
+                config::BackendConfig::S3 { .. } => Box::new(S3Storage::new(&backend_config).await?),
+                config::BackendConfig::WebDav { .. } => Box::new(WebDavStorage::new(&backend_config).await?),
+                config::BackendConfig::Azure { .. } => Box::new(AzureStorage::new(&backend_config).await?),
+                config::BackendConfig::Gcs { .. } => Box::new(GcsStorage::new(&backend_config).await?),
             };
 
             let uploader = ReleaseUploader::new(storage_backend);
-            uploader.upload_release(&package_name, &tag, &files).await?;
+            uploader
+                .upload_release(&package_name, &tag, &files, presign, jobs)
+                .await?;
 
             println!("Successfully uploaded release {} for package {}", tag, package_name);
         }
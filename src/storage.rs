@@ -1,71 +1,221 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use s3::creds::Credentials;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use jsonwebtoken::{encode as jwt_encode, Algorithm, EncodingKey, Header as JwtHeader};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use pin_project_lite::pin_project;
+use reqwest::StatusCode;
 use s3::{Bucket, Region};
+use sha2::Sha256;
 use std::path::Path;
-use std::time::Duration;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, SystemTime};
 use tokio::fs::File;
-use tokio::io::BufReader;
+use tokio::io::{AsyncRead, BufReader, ReadBuf};
+use tokio::sync::Mutex as AsyncMutex;
+use tokio_util::io::ReaderStream;
 
-use crate::config::Config;
+use crate::config::BackendConfig;
+use crate::credentials::CredentialResolver;
+
+/// Metadata about an object that already exists in the backend, used to decide
+/// whether a re-upload can be skipped.
+#[derive(Debug, Clone)]
+pub struct ObjectMeta {
+    pub size: u64,
+    pub sha256: Option<String>,
+}
+
+/// Called with the number of bytes sent since the last call, so a caller can drive
+/// an aggregate progress bar across many concurrent uploads.
+pub type ProgressCallback = Arc<dyn Fn(u64) + Send + Sync>;
+
+pin_project! {
+    /// Wraps an `AsyncRead` and reports each chunk as it's read.
+    struct ProgressReader<R> {
+        #[pin]
+        inner: R,
+        on_read: ProgressCallback,
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for ProgressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut TaskContext<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.project();
+        let before = buf.filled().len();
+        let result = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = result {
+            let read = buf.filled().len() - before;
+            if read > 0 {
+                (this.on_read)(read as u64);
+            }
+        }
+        result
+    }
+}
+
+/// Opens `file_path` and wraps it as a streamed, progress-reporting request body.
+/// Returns the body alongside the file size, which callers need to set
+/// `Content-Length` explicitly since streamed bodies have no length of their own.
+async fn streaming_body(file_path: &Path, on_progress: ProgressCallback) -> Result<(reqwest::Body, u64)> {
+    let file = File::open(file_path)
+        .await
+        .with_context(|| format!("Failed to open file: {}", file_path.display()))?;
+    let file_size = file
+        .metadata()
+        .await
+        .with_context(|| format!("Failed to get file metadata: {}", file_path.display()))?
+        .len();
+
+    let reader = ProgressReader {
+        inner: BufReader::new(file),
+        on_read: on_progress,
+    };
+    Ok((reqwest::Body::wrap_stream(ReaderStream::new(reader)), file_size))
+}
+
+/// Characters to percent-encode within a single path segment: everything but the
+/// RFC 3986 unreserved set. `/` is in this set too; `encode_key_path` keeps it as
+/// the path separator by splitting on it before encoding.
+const PATH_SEGMENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Percent-encodes each `/`-separated segment of an object key before it's spliced
+/// into a request URL.
+fn encode_key_path(key: &str) -> String {
+    key.split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
 
 #[async_trait::async_trait]
 pub trait StorageBackend: Send + Sync {
-    async fn upload_file(&self, key: &str, file_path: &Path, content_type: Option<&str>) -> Result<()>;
-    async fn file_exists(&self, key: &str) -> Result<bool>;
+    /// Upload `file_path` to `key`, tagging the object with its SHA-256 digest where
+    /// the backend supports it. `on_progress` is called with each chunk's byte count
+    /// as it's streamed to the backend.
+    async fn upload_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        sha256: &str,
+        on_progress: ProgressCallback,
+    ) -> Result<()>;
+
+    /// Look up an existing object's size and stored checksum, if any. Returns
+    /// `Ok(None)` when the object doesn't exist.
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>>;
+
+    /// Produce a time-limited download URL for an already-uploaded object. `filename`
+    /// sets a `Content-Disposition: attachment` response header. Backends with no
+    /// notion of presigned URLs (e.g. WebDAV) return `Ok(None)`.
+    async fn presign_download(
+        &self,
+        _key: &str,
+        _expiry: Duration,
+        _filename: Option<&str>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 pub struct S3Storage {
-    bucket: Bucket,
+    bucket_name: String,
+    region: Region,
+    resolver: CredentialResolver,
 }
 
 impl S3Storage {
-    pub async fn new(config: &Config) -> Result<Self> {
-        let credentials = Credentials::new(
-            Some(config.get_access_key()),
-            Some(config.get_secret_key()),
-            None,
-            None,
-            None,
-        )?;
-
-        let region = if let Some(endpoint) = config.get_s3_endpoint() {
+    pub async fn new(config: &BackendConfig) -> Result<Self> {
+        let BackendConfig::S3 {
+            access_key,
+            secret_key,
+            bucket_name,
+            region,
+            endpoint,
+        } = config
+        else {
+            anyhow::bail!("S3Storage requires a BackendConfig::S3 configuration");
+        };
+
+        let region = if let Some(endpoint) = endpoint {
             Region::Custom {
-                region: config.get_aws_region().to_string(),
-                endpoint: endpoint.to_string(),
+                region: region.clone(),
+                endpoint: endpoint.clone(),
             }
         } else {
-            config.get_aws_region().parse().with_context(|| {
-                format!("Invalid AWS region: {}", config.get_aws_region())
-            })?
+            region
+                .parse()
+                .with_context(|| format!("Invalid AWS region: {}", region))?
         };
 
-        let mut bucket = Bucket::new(config.get_bucket_name(), region, credentials)
+        let resolver = CredentialResolver::new(access_key.clone(), secret_key.clone());
+        // Resolve once up front so a missing/misconfigured credential source fails
+        // fast instead of only surfacing on the first upload.
+        resolver.resolve().await?;
+
+        Ok(S3Storage {
+            bucket_name: bucket_name.clone(),
+            region,
+            resolver,
+        })
+    }
+
+    /// Builds a `Bucket` using up-to-date credentials. `extra_headers` is applied on
+    /// top, e.g. to set `x-amz-meta-*` headers for a single upload.
+    async fn bucket(&self, extra_headers: Option<HeaderMap>) -> Result<Bucket> {
+        let credentials = self.resolver.resolve().await?;
+        let mut bucket = Bucket::new(&self.bucket_name, self.region.clone(), credentials)
             .with_context(|| "Failed to create S3 bucket client")?
             .with_path_style(); // Use path-style URLs for better compatibility
-        Ok(S3Storage { bucket: *bucket })
+        if let Some(headers) = extra_headers {
+            bucket = bucket.with_extra_headers(headers);
+        }
+        Ok(*bucket)
     }
 }
 
 #[async_trait]
 impl StorageBackend for S3Storage {
-    async fn upload_file(&self, key: &str, file_path: &Path, content_type: Option<&str>) -> Result<()> {
+    async fn upload_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        sha256: &str,
+        on_progress: ProgressCallback,
+    ) -> Result<()> {
         let file = File::open(file_path).await
             .with_context(|| format!("Failed to open file: {}", file_path.display()))?;
 
-        // Get file size for progress display
-        let file_size = file.metadata().await
-            .with_context(|| format!("Failed to get file metadata: {}", file_path.display()))?
-            .len();
-
         let content_type = content_type.unwrap_or("application/octet-stream");
 
-        println!("Uploading {} ({:.2} MB)...", file_path.display(), file_size as f64 / 1024.0 / 1024.0);
-
         // Use streaming upload for all files - the rust-s3 library handles multipart uploads internally
-        let mut reader = BufReader::new(file);
-        
-        let _response = self.bucket
+        let mut reader = ProgressReader {
+            inner: BufReader::new(file),
+            on_read: on_progress,
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-amz-meta-sha256"),
+            HeaderValue::from_str(sha256).context("Invalid sha256 header value")?,
+        );
+
+        let bucket = self.bucket(Some(headers)).await?;
+        let _response = bucket
             .put_object_stream_with_content_type(&mut reader, key, content_type)
             .await
             .with_context(|| {
@@ -77,15 +227,764 @@ impl StorageBackend for S3Storage {
             })?;
 
         // The rust-s3 library will return an error for non-200 status codes, so we don't need to check it explicitly
-        println!("✓ Uploaded: {} -> s3://{}/{}", file_path.display(), self.bucket.name(), key);
         Ok(())
     }
 
-    async fn file_exists(&self, key: &str) -> Result<bool> {
-        match self.bucket.head_object(key).await {
-            Ok((_, status_code)) => Ok(status_code == 200),
-            Err(s3::error::S3Error::HttpFailWithBody(status, _)) if status == 404 => Ok(false),
-            Err(_) => Err(anyhow::anyhow!("Failed to check if file exists")),
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let bucket = self.bucket(None).await?;
+        match bucket.head_object(key).await {
+            Ok((head, status_code)) if status_code == 200 => Ok(Some(ObjectMeta {
+                size: head.content_length.unwrap_or(0) as u64,
+                sha256: head
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("sha256").cloned()),
+            })),
+            Ok(_) => Ok(None),
+            Err(s3::error::S3Error::HttpFailWithBody(status, _)) if status == 404 => Ok(None),
+            Err(_) => Err(anyhow::anyhow!("Failed to check if file exists: {}", key)),
+        }
+    }
+
+    async fn presign_download(
+        &self,
+        key: &str,
+        expiry: Duration,
+        filename: Option<&str>,
+    ) -> Result<Option<String>> {
+        let custom_queries = filename.map(|name| {
+            let mut queries = std::collections::HashMap::new();
+            queries.insert(
+                "response-content-disposition".to_string(),
+                format!("attachment; filename=\"{}\"", name),
+            );
+            queries
+        });
+
+        let bucket = self.bucket(None).await?;
+        let url = bucket
+            .presign_get(key, expiry.as_secs() as u32, custom_queries)
+            .await
+            .with_context(|| format!("Failed to presign download URL for: {}", key))?;
+
+        Ok(Some(url))
+    }
+}
+
+/// A `WWW-Authenticate: Digest ...` challenge (RFC 7616).
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    qop: Option<String>,
+}
+
+impl DigestChallenge {
+    /// Parses a `Digest realm="...", nonce="...", qop="auth", opaque="..."` header.
+    /// Returns `None` for anything else (e.g. a `Basic` challenge).
+    fn parse(header: &str) -> Option<Self> {
+        let rest = header.strip_prefix("Digest ")?;
+        let mut realm = None;
+        let mut nonce = None;
+        let mut opaque = None;
+        let mut qop = None;
+        for part in rest.split(',') {
+            let Some((key, value)) = part.trim().split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+            match key.trim() {
+                "realm" => realm = Some(value.to_string()),
+                "nonce" => nonce = Some(value.to_string()),
+                "opaque" => opaque = Some(value.to_string()),
+                "qop" => qop = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(DigestChallenge {
+            realm: realm?,
+            nonce: nonce?,
+            opaque,
+            qop,
+        })
+    }
+}
+
+/// Generates a client nonce for digest auth - just needs to be unique per request,
+/// not cryptographically unpredictable.
+fn generate_cnonce() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{:x}", md5::compute(nanos.to_le_bytes()))
+}
+
+/// Storage backend for plain WebDAV servers (Nextcloud, Apache mod_dav, ...) that
+/// don't speak S3. Uses HTTP PUT/HEAD for file transfer and MKCOL to create the
+/// intermediate `package/tag` collections.
+pub struct WebDavStorage {
+    client: reqwest::Client,
+    base_url: String,
+    credentials: Option<(String, String)>,
+}
+
+impl WebDavStorage {
+    pub async fn new(config: &BackendConfig) -> Result<Self> {
+        let BackendConfig::WebDav { url, user, password } = config else {
+            anyhow::bail!("WebDavStorage requires a BackendConfig::WebDav configuration");
+        };
+
+        let credentials = match (user, password) {
+            (Some(user), Some(password)) => Some((user.clone(), password.clone())),
+            _ => None,
+        };
+
+        Ok(WebDavStorage {
+            client: reqwest::Client::new(),
+            base_url: url.trim_end_matches('/').to_string(),
+            credentials,
+        })
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!("{}/{}", self.base_url, encode_key_path(key))
+    }
+
+    /// Computes an RFC 7616 `Authorization: Digest` header for `method`/`url` against
+    /// a challenge obtained from that same URL. `qop=auth-int` isn't supported, since
+    /// the request body here is streamed rather than buffered.
+    fn digest_authorization(
+        user: &str,
+        password: &str,
+        method: &reqwest::Method,
+        url: &str,
+        challenge: &DigestChallenge,
+    ) -> Result<String> {
+        let use_qop = match &challenge.qop {
+            None => false,
+            Some(qop) if qop.split(',').any(|q| q.trim() == "auth") => true,
+            Some(qop) => anyhow::bail!("WebDAV server requires an unsupported digest qop: {}", qop),
+        };
+
+        let parsed = reqwest::Url::parse(url).with_context(|| format!("Invalid WebDAV URL: {}", url))?;
+        let digest_uri = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+
+        let ha1 = format!("{:x}", md5::compute(format!("{}:{}:{}", user, challenge.realm, password)));
+        let ha2 = format!("{:x}", md5::compute(format!("{}:{}", method.as_str(), digest_uri)));
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\"",
+            user, challenge.realm, challenge.nonce, digest_uri
+        );
+
+        if use_qop {
+            let cnonce = generate_cnonce();
+            let nc = "00000001";
+            let response = md5::compute(format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, challenge.nonce, nc, cnonce, ha2
+            ));
+            header.push_str(&format!(
+                ", response=\"{:x}\", qop=auth, nc={}, cnonce=\"{}\"",
+                response, nc, cnonce
+            ));
+        } else {
+            let response = md5::compute(format!("{}:{}:{}", ha1, challenge.nonce, ha2));
+            header.push_str(&format!(", response=\"{:x}\"", response));
+        }
+
+        if let Some(opaque) = &challenge.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+
+        Ok(header)
+    }
+
+    /// Builds an authenticated request for `method`/`url`. When credentials are
+    /// configured, this first sends a bodyless probe to learn whether the server
+    /// wants Basic or Digest - probing first (rather than retrying after a 401)
+    /// keeps this safe to use with a request whose body is streamed from disk.
+    async fn authenticated_request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let builder = self.client.request(method.clone(), url);
+        let Some((user, password)) = &self.credentials else {
+            return Ok(builder);
+        };
+
+        let probe = self
+            .client
+            .request(reqwest::Method::HEAD, url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to probe WebDAV auth requirements: {}", url))?;
+
+        if probe.status() != StatusCode::UNAUTHORIZED {
+            return Ok(builder.basic_auth(user, Some(password)));
+        }
+
+        let digest_challenge = probe
+            .headers()
+            .get_all(reqwest::header::WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(DigestChallenge::parse);
+
+        match digest_challenge {
+            Some(challenge) => {
+                let auth = Self::digest_authorization(user, password, &method, url, &challenge)?;
+                Ok(builder.header(reqwest::header::AUTHORIZATION, auth))
+            }
+            None => Ok(builder.basic_auth(user, Some(password))),
+        }
+    }
+
+    /// Create each intermediate collection (directory) in `key`'s path that doesn't
+    /// already exist. WebDAV has no notion of implicitly-created parent directories,
+    /// so `package/tag/filename` needs `MKCOL package` then `MKCOL package/tag`.
+    async fn ensure_collections(&self, key: &str) -> Result<()> {
+        let mut segments: Vec<&str> = key.split('/').collect();
+        segments.pop(); // drop the filename itself
+
+        let mut path = String::new();
+        for segment in segments {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(segment);
+
+            let url = self.url_for(&path);
+            let response = self
+                .authenticated_request(reqwest::Method::from_bytes(b"MKCOL").unwrap(), &url)
+                .await?
+                .send()
+                .await
+                .with_context(|| format!("Failed to create WebDAV collection: {}", path))?;
+
+            // 201 Created, or 405 Method Not Allowed because it already exists - both fine.
+            match response.status() {
+                StatusCode::CREATED | StatusCode::METHOD_NOT_ALLOWED => {}
+                status => {
+                    anyhow::bail!("Failed to create WebDAV collection '{}': {}", path, status)
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for WebDavStorage {
+    async fn upload_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        sha256: &str,
+        on_progress: ProgressCallback,
+    ) -> Result<()> {
+        self.ensure_collections(key).await?;
+
+        let (body, body_len) = streaming_body(file_path, on_progress).await?;
+        let content_type = content_type.unwrap_or("application/octet-stream");
+        let url = self.url_for(key);
+
+        let response = self
+            .authenticated_request(reqwest::Method::PUT, &url)
+            .await?
+            .header("Content-Type", content_type)
+            .header("Content-Length", body_len)
+            .header("X-Checksum-SHA256", sha256)
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to upload file to WebDAV: {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "WebDAV upload failed for {}: {}",
+                key,
+                response.status()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let url = self.url_for(key);
+        let response = self
+            .authenticated_request(reqwest::Method::HEAD, &url)
+            .await?
+            .send()
+            .await
+            .with_context(|| format!("Failed to check if file exists on WebDAV: {}", url))?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let size = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                // Not every WebDAV server persists custom headers across requests; when
+                // it doesn't, this comes back None and the caller safely re-uploads.
+                let sha256 = response
+                    .headers()
+                    .get("X-Checksum-SHA256")
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string());
+
+                Ok(Some(ObjectMeta { size, sha256 }))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => anyhow::bail!("Failed to check if file exists on WebDAV: {}", status),
+        }
+    }
+}
+
+/// API version pinned for both the request header and the Shared Key signature -
+/// these must always match, since the version is itself part of what gets signed.
+const AZURE_API_VERSION: &str = "2021-08-06";
+
+/// Storage backend for Azure Blob Storage. Uploads are a single PUT Block Blob
+/// request, using either a SAS token appended to the URL or an account SharedKey
+/// signed per request, whichever the config provides.
+pub struct AzureStorage {
+    client: reqwest::Client,
+    account: String,
+    container: String,
+    access_key: Option<String>,
+    sas_token: Option<String>,
+    base_url: String,
+}
+
+impl AzureStorage {
+    pub async fn new(config: &BackendConfig) -> Result<Self> {
+        let BackendConfig::Azure {
+            account,
+            container,
+            access_key,
+            sas_token,
+            endpoint,
+        } = config
+        else {
+            anyhow::bail!("AzureStorage requires a BackendConfig::Azure configuration");
+        };
+
+        if access_key.is_none() && sas_token.is_none() {
+            anyhow::bail!(
+                "Azure storage requires either AZURE_STORAGE_KEY (SharedKey auth) or \
+                AZURE_STORAGE_SAS_TOKEN"
+            );
+        }
+
+        let base_url = endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://{}.blob.core.windows.net", account));
+
+        Ok(AzureStorage {
+            client: reqwest::Client::new(),
+            account: account.clone(),
+            container: container.clone(),
+            access_key: access_key.clone(),
+            sas_token: sas_token.clone(),
+            base_url,
+        })
+    }
+
+    fn blob_url(&self, key: &str) -> String {
+        let mut url = format!("{}/{}/{}", self.base_url, self.container, encode_key_path(key));
+        if let Some(sas_token) = &self.sas_token {
+            url.push('?');
+            url.push_str(sas_token.trim_start_matches('?'));
+        }
+        url
+    }
+
+    /// Signs a request per Azure's Shared Key authorization scheme. `ms_headers` must
+    /// list every `x-ms-*` header actually set on the request being signed, or Azure's
+    /// recomputed signature won't match and the request fails with
+    /// `AuthenticationFailed`.
+    fn shared_key_auth(
+        &self,
+        method: &str,
+        key: &str,
+        content_length: u64,
+        content_type: &str,
+        ms_headers: &[(&str, &str)],
+    ) -> Result<Option<String>> {
+        if self.sas_token.is_some() {
+            return Ok(None);
+        }
+        let Some(access_key) = &self.access_key else {
+            return Ok(None);
+        };
+
+        let content_length = if content_length == 0 {
+            String::new()
+        } else {
+            content_length.to_string()
+        };
+
+        let mut ms_headers = ms_headers.to_vec();
+        ms_headers.sort_by_key(|(name, _)| *name);
+        let canonicalized_headers: String = ms_headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value))
+            .collect();
+        let canonicalized_resource = format!("/{}/{}/{}", self.account, self.container, key);
+
+        let string_to_sign = format!(
+            "{}\n\n\n{}\n\n{}\n\n\n\n\n\n\n{}{}",
+            method, content_length, content_type, canonicalized_headers, canonicalized_resource
+        );
+
+        let key_bytes = base64::engine::general_purpose::STANDARD
+            .decode(access_key)
+            .context("Invalid AZURE_STORAGE_KEY: not valid base64")?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(&key_bytes)
+            .context("Invalid AZURE_STORAGE_KEY: wrong length for HMAC-SHA256")?;
+        mac.update(string_to_sign.as_bytes());
+        let signature = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+
+        Ok(Some(format!("SharedKey {}:{}", self.account, signature)))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for AzureStorage {
+    async fn upload_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        sha256: &str,
+        on_progress: ProgressCallback,
+    ) -> Result<()> {
+        let (body, body_len) = streaming_body(file_path, on_progress).await?;
+        let content_type = content_type.unwrap_or("application/octet-stream");
+        let date = httpdate::fmt_http_date(SystemTime::now());
+        let url = self.blob_url(key);
+
+        let mut request = self
+            .client
+            .put(&url)
+            .header("x-ms-blob-type", "BlockBlob")
+            .header("x-ms-version", AZURE_API_VERSION)
+            .header("x-ms-date", &date)
+            .header("x-ms-meta-sha256", sha256)
+            .header("Content-Type", content_type)
+            .header("Content-Length", body_len);
+
+        if let Some(auth) = self.shared_key_auth(
+            "PUT",
+            key,
+            body_len,
+            content_type,
+            &[
+                ("x-ms-blob-type", "BlockBlob"),
+                ("x-ms-date", &date),
+                ("x-ms-meta-sha256", sha256),
+                ("x-ms-version", AZURE_API_VERSION),
+            ],
+        )? {
+            request = request.header("Authorization", auth);
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to upload blob to Azure: {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Azure blob upload failed for {}: {}", key, response.status());
+        }
+
+        Ok(())
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let date = httpdate::fmt_http_date(SystemTime::now());
+        let url = self.blob_url(key);
+
+        let mut request = self
+            .client
+            .head(&url)
+            .header("x-ms-version", AZURE_API_VERSION)
+            .header("x-ms-date", &date);
+
+        if let Some(auth) = self.shared_key_auth(
+            "HEAD",
+            key,
+            0,
+            "",
+            &[("x-ms-date", &date), ("x-ms-version", AZURE_API_VERSION)],
+        )? {
+            request = request.header("Authorization", auth);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to check if blob exists on Azure: {}", url))?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let size = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                let sha256 = response
+                    .headers()
+                    .get("x-ms-meta-sha256")
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string());
+
+                Ok(Some(ObjectMeta { size, sha256 }))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => anyhow::bail!("Failed to check if blob exists on Azure: {}", status),
+        }
+    }
+}
+
+/// A GCS service account key, as downloaded from the Cloud Console. Only the fields
+/// needed to sign a JWT and exchange it for an access token are kept.
+#[derive(serde::Deserialize)]
+struct GcsServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    #[serde(default = "default_gcs_token_uri")]
+    token_uri: String,
+}
+
+fn default_gcs_token_uri() -> String {
+    "https://oauth2.googleapis.com/token".to_string()
+}
+
+#[derive(serde::Serialize)]
+struct GcsJwtClaims {
+    iss: String,
+    scope: String,
+    aud: String,
+    iat: u64,
+    exp: u64,
+}
+
+/// A cached OAuth access token, refreshed once it's close to expiring.
+struct GcsTokenCache {
+    token: String,
+    expires_at: SystemTime,
+}
+
+/// Storage backend for Google Cloud Storage. Uploads go through the XML API (a
+/// single PUT per object) rather than the JSON resumable upload protocol. When a
+/// service account key is configured, its private key signs a JWT that's exchanged
+/// for a short-lived OAuth access token; without one, requests are unauthenticated
+/// (e.g. against an emulator).
+pub struct GcsStorage {
+    client: reqwest::Client,
+    bucket_name: String,
+    base_url: String,
+    service_account: Option<GcsServiceAccountKey>,
+    token_cache: AsyncMutex<Option<GcsTokenCache>>,
+}
+
+impl GcsStorage {
+    pub async fn new(config: &BackendConfig) -> Result<Self> {
+        let BackendConfig::Gcs {
+            bucket_name,
+            service_account_key,
+            endpoint,
+        } = config
+        else {
+            anyhow::bail!("GcsStorage requires a BackendConfig::Gcs configuration");
+        };
+
+        let service_account = service_account_key
+            .as_deref()
+            .map(load_gcs_service_account)
+            .transpose()?;
+
+        let base_url = endpoint
+            .clone()
+            .unwrap_or_else(|| "https://storage.googleapis.com".to_string());
+
+        Ok(GcsStorage {
+            client: reqwest::Client::new(),
+            bucket_name: bucket_name.clone(),
+            base_url,
+            service_account,
+            token_cache: AsyncMutex::new(None),
+        })
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!("{}/{}/{}", self.base_url, self.bucket_name, encode_key_path(key))
+    }
+
+    /// Exchanges the service account's private key for a short-lived OAuth access
+    /// token via the JWT bearer grant, caching it until shortly before it expires.
+    /// Returns `None` when no service account is configured.
+    async fn access_token(&self) -> Result<Option<String>> {
+        let Some(service_account) = &self.service_account else {
+            return Ok(None);
+        };
+
+        let mut cache = self.token_cache.lock().await;
+        if let Some(cached) = cache.as_ref() {
+            if SystemTime::now() < cached.expires_at {
+                return Ok(Some(cached.token.clone()));
+            }
+        }
+
+        let now = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = GcsJwtClaims {
+            iss: service_account.client_email.clone(),
+            scope: "https://www.googleapis.com/auth/devstorage.read_write".to_string(),
+            aud: service_account.token_uri.clone(),
+            iat: now,
+            exp: now + 3600,
+        };
+
+        let key = EncodingKey::from_rsa_pem(service_account.private_key.as_bytes())
+            .context("Invalid GCS service account private key")?;
+        let assertion = jwt_encode(&JwtHeader::new(Algorithm::RS256), &claims, &key)
+            .context("Failed to sign GCS service account JWT")?;
+
+        let response = self
+            .client
+            .post(&service_account.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .context("Failed to exchange GCS service account JWT for an access token")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("GCS token exchange failed: {}", response.status());
+        }
+
+        #[derive(serde::Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            expires_in: u64,
+        }
+        let token_response: TokenResponse = response
+            .json()
+            .await
+            .context("Failed to parse GCS token exchange response")?;
+
+        let expires_at =
+            SystemTime::now() + Duration::from_secs(token_response.expires_in.saturating_sub(60));
+        let token = token_response.access_token.clone();
+        *cache = Some(GcsTokenCache {
+            token: token_response.access_token,
+            expires_at,
+        });
+
+        Ok(Some(token))
+    }
+}
+
+/// Loads a GCS service account key from either a file path or an inline JSON blob,
+/// so `GCS_SERVICE_ACCOUNT_KEY` can point at a key file or embed the key directly.
+fn load_gcs_service_account(value: &str) -> Result<GcsServiceAccountKey> {
+    let json = if Path::new(value).is_file() {
+        std::fs::read_to_string(value)
+            .with_context(|| format!("Failed to read GCS service account key file: {}", value))?
+    } else {
+        value.to_string()
+    };
+    serde_json::from_str(&json).context("Failed to parse GCS service account key as JSON")
+}
+
+#[async_trait]
+impl StorageBackend for GcsStorage {
+    async fn upload_file(
+        &self,
+        key: &str,
+        file_path: &Path,
+        content_type: Option<&str>,
+        sha256: &str,
+        on_progress: ProgressCallback,
+    ) -> Result<()> {
+        let (body, body_len) = streaming_body(file_path, on_progress).await?;
+        let content_type = content_type.unwrap_or("application/octet-stream");
+        let url = self.object_url(key);
+
+        let mut request = self
+            .client
+            .put(&url)
+            .header("Content-Type", content_type)
+            .header("Content-Length", body_len)
+            .header("x-goog-meta-sha256", sha256);
+
+        if let Some(token) = self.access_token().await? {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to upload object to GCS: {}", url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("GCS upload failed for {}: {}", key, response.status());
+        }
+
+        Ok(())
+    }
+
+    async fn head(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let url = self.object_url(key);
+        let mut request = self.client.head(&url);
+
+        if let Some(token) = self.access_token().await? {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to check if object exists on GCS: {}", url))?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let size = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                let sha256 = response
+                    .headers()
+                    .get("x-goog-meta-sha256")
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string());
+
+                Ok(Some(ObjectMeta { size, sha256 }))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => anyhow::bail!("Failed to check if object exists on GCS: {}", status),
         }
     }
 }
\ No newline at end of file
@@ -1,12 +1,23 @@
 use anyhow::{Context, Result};
+use futures::future::join_all;
 use glob::glob;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::sync::Semaphore;
 
 use crate::storage::StorageBackend;
 
+/// Default number of assets uploaded concurrently when `--jobs` isn't set.
+pub const DEFAULT_JOBS: usize = 4;
+
 pub struct ReleaseUploader {
-    storage: Box<dyn StorageBackend>,
+    storage: Arc<dyn StorageBackend>,
 }
 
 #[derive(Debug, Clone)]
@@ -16,9 +27,28 @@ pub struct Asset {
     pub s3_key: String,
 }
 
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    name: String,
+    size: u64,
+    content_type: String,
+    sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    package: String,
+    tag: String,
+    assets: Vec<ManifestEntry>,
+}
+
 impl ReleaseUploader {
     pub fn new(storage: Box<dyn StorageBackend>) -> Self {
-        Self { storage }
+        Self {
+            storage: Arc::from(storage),
+        }
     }
 
     pub async fn upload_release(
@@ -26,6 +56,8 @@ impl ReleaseUploader {
         package_name: &str,
         tag: &str,
         file_patterns: &[String],
+        presign: Option<Duration>,
+        jobs: usize,
     ) -> Result<()> {
         let assets = self.discover_assets(package_name, tag, file_patterns)?;
 
@@ -33,21 +65,196 @@ impl ReleaseUploader {
             anyhow::bail!("No assets found matching the specified patterns");
         }
 
-        println!("Found {} assets to upload:", assets.len());
+        println!("Found {} assets to upload ({} concurrent job(s)):", assets.len(), jobs);
         for asset in &assets {
             println!("  {} -> {}", asset.file_path.display(), asset.s3_key);
         }
 
-        for asset in assets {
-            let content_type = guess_content_type(&asset.file_path);
+        let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+        let multi_progress = MultiProgress::new();
+        let multi_progress = &multi_progress;
+
+        let uploads = assets.iter().map(|asset| {
+            let semaphore = Arc::clone(&semaphore);
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("upload semaphore is never closed");
+                let result = self.upload_asset(asset, presign, multi_progress).await;
+                (asset, result)
+            }
+        });
+
+        let outcomes = join_all(uploads).await;
+
+        let mut manifest_entries = Vec::with_capacity(outcomes.len());
+        let mut failures = Vec::new();
+        for (asset, result) in outcomes {
+            match result {
+                Ok(entry) => manifest_entries.push(entry),
+                Err(err) => failures.push((asset, err)),
+            }
+        }
+
+        if !failures.is_empty() {
+            eprintln!("Failed to upload {} of {} asset(s):", failures.len(), assets.len());
+            for (asset, err) in &failures {
+                eprintln!("  {}: {:#}", asset.file_path.display(), err);
+            }
+        }
+
+        if !manifest_entries.is_empty() {
+            self.upload_manifest(package_name, tag, manifest_entries, presign)
+                .await?;
+        }
+
+        if !failures.is_empty() {
+            anyhow::bail!(
+                "{} of {} assets failed to upload",
+                failures.len(),
+                assets.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Uploads a single asset, skipping the transfer when an object already exists
+    /// at the same key with a matching SHA-256, and returns its manifest entry.
+    async fn upload_asset(
+        &self,
+        asset: &Asset,
+        presign: Option<Duration>,
+        multi_progress: &MultiProgress,
+    ) -> Result<ManifestEntry> {
+        let content_type = guess_content_type(&asset.file_path).unwrap_or("application/octet-stream");
+        let (sha256, short_hash) = checksum_file(&asset.file_path)
+            .await
+            .with_context(|| format!("Failed to checksum asset: {}", asset.file_path.display()))?;
+        let size = tokio::fs::metadata(&asset.file_path)
+            .await
+            .with_context(|| format!("Failed to read metadata for asset: {}", asset.file_path.display()))?
+            .len();
+
+        let name = asset
+            .file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let bar = multi_progress.add(ProgressBar::new(size));
+        bar.set_style(
+            ProgressStyle::with_template("{msg:.cyan} [{bar:30}] {bytes}/{total_bytes}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+        );
+        bar.set_message(name.clone());
+
+        let existing = self.storage.head(&asset.s3_key).await?;
+        let unchanged = existing
+            .as_ref()
+            .and_then(|meta| meta.sha256.as_deref())
+            .is_some_and(|existing_sha256| existing_sha256 == sha256);
+
+        if unchanged {
+            bar.set_position(size);
+            bar.finish_with_message(format!("{} (unchanged, sha256 {})", name, short_hash));
+        } else {
+            let bar_for_progress = bar.clone();
+            let on_progress: crate::storage::ProgressCallback =
+                Arc::new(move |delta| bar_for_progress.inc(delta));
+
             self.storage
-                .upload_file(&asset.s3_key, &asset.file_path, content_type)
+                .upload_file(
+                    &asset.s3_key,
+                    &asset.file_path,
+                    Some(content_type),
+                    &sha256,
+                    on_progress,
+                )
                 .await
                 .with_context(|| {
                     format!("Failed to upload asset: {}", asset.file_path.display())
                 })?;
+
+            bar.finish_with_message(format!("{} uploaded", name));
+        }
+
+        let url = match presign {
+            Some(expiry) => self
+                .storage
+                .presign_download(&asset.s3_key, expiry, asset.display_name.as_deref())
+                .await
+                .with_context(|| {
+                    format!("Failed to presign download URL for: {}", asset.s3_key)
+                })?,
+            None => None,
+        };
+
+        if let Some(url) = &url {
+            println!("  Download URL: {}", url);
+        }
+
+        Ok(ManifestEntry {
+            name,
+            size,
+            content_type: content_type.to_string(),
+            sha256,
+            url,
+        })
+    }
+
+    /// Builds the release manifest, writes it to a temp file, and uploads it to
+    /// `package/tag/manifest.json` so a release can be inspected or re-verified
+    /// without re-downloading every asset.
+    async fn upload_manifest(
+        &self,
+        package_name: &str,
+        tag: &str,
+        assets: Vec<ManifestEntry>,
+        presign: Option<Duration>,
+    ) -> Result<()> {
+        let manifest = Manifest {
+            package: package_name.to_string(),
+            tag: tag.to_string(),
+            assets,
+        };
+        let json = serde_json::to_vec_pretty(&manifest).context("Failed to serialize manifest")?;
+
+        let manifest_path =
+            std::env::temp_dir().join(format!("{}-{}-manifest.json", package_name, tag));
+        tokio::fs::write(&manifest_path, &json)
+            .await
+            .with_context(|| format!("Failed to write manifest: {}", manifest_path.display()))?;
+
+        let (sha256, _short_hash) = checksum_file(&manifest_path).await?;
+        let manifest_key = format!("{}/{}/manifest.json", package_name, tag);
+
+        self.storage
+            .upload_file(
+                &manifest_key,
+                &manifest_path,
+                Some("application/json"),
+                &sha256,
+                Arc::new(|_| {}),
+            )
+            .await
+            .with_context(|| "Failed to upload release manifest")?;
+
+        if let Some(expiry) = presign {
+            if let Some(url) = self
+                .storage
+                .presign_download(&manifest_key, expiry, Some("manifest.json"))
+                .await?
+            {
+                println!("  Manifest: {}", url);
+            }
         }
 
+        let _ = tokio::fs::remove_file(&manifest_path).await;
+
         Ok(())
     }
 
@@ -152,6 +359,35 @@ impl ReleaseUploader {
     }
 }
 
+/// Computes the SHA-256 of `path` in fixed-size chunks (so we never hold the whole
+/// file in memory), plus a short 64-bit hash for easier eyeballing in progress
+/// output. Reads the file a second time ahead of the actual upload because every
+/// backend needs the checksum as a request header, sent before the streamed body.
+async fn checksum_file(path: &Path) -> Result<(String, String)> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Failed to open file for checksum: {}", path.display()))?;
+
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let bytes_read = file
+            .read(&mut buf)
+            .await
+            .with_context(|| format!("Failed to read file for checksum: {}", path.display()))?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buf[..bytes_read]);
+    }
+
+    let digest = hasher.finalize();
+    let sha256 = format!("{:x}", digest);
+    let short_hash = format!("{:016x}", u64::from_be_bytes(digest[..8].try_into().unwrap()));
+
+    Ok((sha256, short_hash))
+}
+
 fn guess_content_type(file_path: &Path) -> Option<&'static str> {
     match file_path.extension().and_then(|ext| ext.to_str()) {
         Some("zip") => Some("application/zip"),